@@ -0,0 +1,22 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use sudoku::Sudoku;
+
+// A puzzle with very few givens, forcing deep backtracking.
+const HARD_9X9: &str = "\
+800000000\
+003600000\
+070090200\
+050007000\
+000045700\
+000100030\
+001000068\
+008500010\
+090000400";
+
+fn solve_hard_9x9(c: &mut Criterion) {
+    let sudoku: Sudoku = HARD_9X9.parse().unwrap();
+    c.bench_function("solve hard 9x9", |b| b.iter(|| sudoku.solve()));
+}
+
+criterion_group!(benches, solve_hard_9x9);
+criterion_main!(benches);
@@ -0,0 +1,474 @@
+use anyhow::{bail, Result};
+use std::{fmt, str::FromStr};
+
+/// Bitmask over values 1..=n: bit `k` set means value `k` is still possible.
+type Domain = u32;
+
+fn full_domain(n: u8) -> Domain {
+    ((1u32 << (n + 1)) - 1) & !1
+}
+
+fn domain_values(domain: Domain, n: u8) -> impl Iterator<Item = u8> {
+    (1..=n).filter(move |val| domain & (1 << val) != 0)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Variable {
+    Assigned(u8),
+    Unassigned(Domain),
+}
+
+type Assignment = Vec<Variable>;
+
+fn value_from_char(c: char, n: u8) -> Result<Option<u8>> {
+    let val = match c {
+        '0' => return Ok(None),
+        '1'..='9' => c.to_digit(10).unwrap() as u8,
+        'A'..='Z' => 10 + (c as u8 - b'A'),
+        _ => bail!("invalid character '{c}' in input"),
+    };
+    if val > n {
+        bail!("value '{c}' is out of range for a grid side of {n}");
+    }
+    Ok(Some(val))
+}
+
+fn char_from_value(val: u8) -> char {
+    if val < 10 {
+        (b'0' + val) as char
+    } else {
+        (b'A' + val - 10) as char
+    }
+}
+
+/// Call/failure counts accumulated by `backtrack`, useful for gauging how hard a puzzle was.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchStats {
+    pub called: i32,
+    pub failed: i32,
+}
+
+/// A sudoku puzzle with box width `b` (grid side `n = b*b`, so `b = 3` is the
+/// familiar 9x9 grid), either partially or fully filled in.
+#[derive(Clone, Debug)]
+pub struct Sudoku {
+    b: usize,
+    assignment: Assignment,
+}
+
+impl FromStr for Sudoku {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let n = (chars.len() as f64).sqrt().round() as usize;
+        if n * n != chars.len() {
+            bail!("invalid length of input");
+        }
+        let b = (n as f64).sqrt().round() as usize;
+        if b * b != n {
+            bail!("grid side {n} is not a square of a box width");
+        }
+        let domain = full_domain(n as u8);
+        let assignment = chars
+            .into_iter()
+            .map(|c| match value_from_char(c, n as u8)? {
+                None => Ok(Variable::Unassigned(domain)),
+                Some(val) => Ok(Variable::Assigned(val)),
+            })
+            .collect::<Result<Assignment>>()?;
+        Ok(Sudoku { b, assignment })
+    }
+}
+
+impl fmt::Display for Sudoku {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.b * self.b;
+        for (i, row) in self.assignment.chunks(n).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for v in row {
+                match v {
+                    Variable::Assigned(val) => write!(f, "{}", char_from_value(*val))?,
+                    Variable::Unassigned(_) => write!(f, " ")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sudoku {
+    /// Finds a solution via AC-3 constraint propagation plus backtracking search.
+    pub fn solve(&self) -> Option<Sudoku> {
+        self.solve_with_stats().map(|(sudoku, _stats)| sudoku)
+    }
+
+    /// Like `solve`, but also returns the `backtrack` call/failure counts.
+    pub fn solve_with_stats(&self) -> Option<(Sudoku, SearchStats)> {
+        let (solutions, stats) = self.solve_all_with_stats(1);
+        solutions.into_iter().next().map(|sudoku| (sudoku, stats))
+    }
+
+    /// Enumerates up to `limit` distinct solutions.
+    pub fn solve_all(&self, limit: usize) -> Vec<Sudoku> {
+        self.solve_all_with_stats(limit).0
+    }
+
+    /// Returns whether the puzzle has exactly one solution, by convention the
+    /// requirement for a well-formed puzzle.
+    pub fn is_unique(&self) -> bool {
+        self.solve_all(2).len() == 1
+    }
+
+    fn solve_all_with_stats(&self, limit: usize) -> (Vec<Sudoku>, SearchStats) {
+        let mut stats = SearchStats::default();
+        let mut solutions = Vec::new();
+        let mut assignment = self.assignment.clone();
+        let mut undo = Vec::new();
+        let constraints = Constraints::new(self.b);
+        backtrack(
+            &mut assignment,
+            &constraints,
+            &mut stats,
+            limit,
+            &mut solutions,
+            &mut undo,
+        );
+        let solutions = solutions
+            .into_iter()
+            .map(|assignment| Sudoku {
+                b: self.b,
+                assignment,
+            })
+            .collect();
+        (solutions, stats)
+    }
+}
+
+fn assigned_variables(assignment: &Assignment) -> Vec<(usize, u8)> {
+    assignment
+        .iter()
+        .enumerate()
+        .filter_map(|(x, var)| match var {
+            Variable::Assigned(val) => Some((x, *val)),
+            Variable::Unassigned(_) => None,
+        })
+        .collect()
+}
+
+fn unassigned_variable(assignment: &Assignment) -> Option<(usize, Domain)> {
+    assignment
+        .iter()
+        .enumerate()
+        .filter_map(|(x, var)| match var {
+            Variable::Assigned(_) => None,
+            Variable::Unassigned(d) => Some((x, *d)),
+        })
+        .min_by_key(|(_x, d)| d.count_ones())
+}
+
+fn generate_constraints(x: usize, b: usize) -> Vec<usize> {
+    let n = b * b;
+    let mut constraints = Vec::with_capacity(3 * n);
+    let (col, row) = (x % n, x / n);
+    for offset in 0..n {
+        let i = col + offset * n;
+        if x != i {
+            constraints.push(i);
+        }
+
+        let i = row * n + offset;
+        if x != i {
+            constraints.push(i);
+        }
+    }
+
+    let (box_base_col, box_base_row) = (b * (col / b), b * (row / b));
+    for col_offset in 0..b {
+        if box_base_col + col_offset == col {
+            continue;
+        }
+        for row_offset in 0..b {
+            if box_base_row + row_offset == row {
+                continue;
+            }
+            let i = (box_base_row + row_offset) * n + box_base_col + col_offset;
+            if x != i {
+                constraints.push(i);
+            }
+        }
+    }
+    constraints
+}
+
+/// Per-cell neighbor lists and per-unit cell lists for a grid of box width
+/// `b`, computed once per solve rather than rebuilt at every `backtrack` node.
+struct Constraints {
+    n: u8,
+    neighbors: Vec<Vec<usize>>,
+    units: Vec<Vec<usize>>,
+}
+
+impl Constraints {
+    fn new(b: usize) -> Self {
+        let n = b * b;
+        let neighbors = (0..n * n).map(|x| generate_constraints(x, b)).collect();
+        Constraints {
+            n: n as u8,
+            neighbors,
+            units: units(b),
+        }
+    }
+}
+
+/// Records the prior value of each cell a propagation pass touches, so a
+/// failed branch can be undone in place instead of working from a clone.
+type Undo = Vec<(usize, Variable)>;
+
+fn set(assignment: &mut Assignment, undo: &mut Undo, cell: usize, new: Variable) {
+    undo.push((cell, assignment[cell]));
+    assignment[cell] = new;
+}
+
+fn backtrack(
+    assignment: &mut Assignment,
+    constraints: &Constraints,
+    stats: &mut SearchStats,
+    limit: usize,
+    solutions: &mut Vec<Assignment>,
+    undo: &mut Undo,
+) {
+    stats.called += 1;
+    if solutions.len() >= limit {
+        return;
+    }
+    match unassigned_variable(assignment) {
+        Some((x, domain)) => {
+            for val in domain_values(domain, constraints.n) {
+                if solutions.len() >= limit {
+                    return;
+                }
+                let checkpoint = undo.len();
+                set(assignment, undo, x, Variable::Assigned(val));
+                match propagate(assignment, constraints, undo) {
+                    Ok(()) => backtrack(assignment, constraints, stats, limit, solutions, undo),
+                    Err(_) => stats.failed += 1,
+                }
+                while undo.len() > checkpoint {
+                    let (cell, before) = undo.pop().unwrap();
+                    assignment[cell] = before;
+                }
+            }
+        }
+        None => solutions.push(assignment.clone()),
+    }
+}
+
+fn ac3(assignment: &mut Assignment, constraints: &Constraints, undo: &mut Undo) -> Result<()> {
+    let mut queue = assigned_variables(assignment);
+    while let Some((x, val)) = queue.pop() {
+        for &y in &constraints.neighbors[x] {
+            match assignment[y] {
+                Variable::Assigned(assigned) if assigned == val => bail!("inconsistent"),
+                Variable::Assigned(_) => (),
+                Variable::Unassigned(domain) => {
+                    // if we remove value from domain such that the variable is assigned, we change it to assigned
+                    let new_domain = domain & !(1 << val);
+                    if new_domain != domain {
+                        if new_domain.count_ones() == 1 {
+                            let val = new_domain.trailing_zeros() as u8;
+                            set(assignment, undo, y, Variable::Assigned(val));
+                            queue.push((y, val));
+                        } else {
+                            set(assignment, undo, y, Variable::Unassigned(new_domain));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// All rows, columns, and boxes, each as the list of cell indices it contains.
+fn units(b: usize) -> Vec<Vec<usize>> {
+    let n = b * b;
+    let mut units = Vec::with_capacity(3 * n);
+    for row in 0..n {
+        units.push((0..n).map(|col| row * n + col).collect());
+    }
+    for col in 0..n {
+        units.push((0..n).map(|row| row * n + col).collect());
+    }
+    for box_row in 0..b {
+        for box_col in 0..b {
+            let unit = (0..b)
+                .flat_map(|row_offset| {
+                    (0..b).map(move |col_offset| {
+                        (box_row * b + row_offset) * n + box_col * b + col_offset
+                    })
+                })
+                .collect();
+            units.push(unit);
+        }
+    }
+    units
+}
+
+/// Assigns every hidden single: a value whose bit appears in exactly one
+/// cell's domain within a unit. Returns whether any assignment was made, and
+/// fails if some unit has a value with no candidate cell at all.
+fn assign_hidden_singles(
+    assignment: &mut Assignment,
+    constraints: &Constraints,
+    undo: &mut Undo,
+) -> Result<bool> {
+    let mut changed = false;
+    for unit in &constraints.units {
+        for val in 1..=constraints.n {
+            let mut candidate_cells = unit.iter().copied().filter(|&cell| {
+                matches!(assignment[cell], Variable::Unassigned(domain) if domain & (1 << val) != 0)
+            });
+            let already_placed = unit.iter().any(
+                |&cell| matches!(assignment[cell], Variable::Assigned(assigned) if assigned == val),
+            );
+            if already_placed {
+                continue;
+            }
+            let Some(cell) = candidate_cells.next() else {
+                bail!("contradiction: no candidate cell for a value in a unit");
+            };
+            if candidate_cells.next().is_none() {
+                set(assignment, undo, cell, Variable::Assigned(val));
+                changed = true;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Runs AC-3 and hidden-single detection to a fixpoint, failing as soon as
+/// either pass finds a contradiction.
+fn propagate(
+    assignment: &mut Assignment,
+    constraints: &Constraints,
+    undo: &mut Undo,
+) -> Result<()> {
+    loop {
+        ac3(assignment, constraints, undo)?;
+        if assignment
+            .iter()
+            .any(|var| matches!(var, Variable::Unassigned(0)))
+        {
+            bail!("contradiction: emptied domain");
+        }
+        if !assign_hidden_singles(assignment, constraints, undo)? {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY_9X9: &str = "\
+530070000\
+600195000\
+098000060\
+800060003\
+400803001\
+700020006\
+060000280\
+000419005\
+000080079";
+
+    const HARD_9X9: &str = "\
+800000000\
+003600000\
+070090200\
+050007000\
+000045700\
+000100030\
+001000068\
+008500010\
+090000400";
+
+    const UNSOLVABLE_9X9: &str = "\
+110000000\
+000000000\
+000000000\
+000000000\
+000000000\
+000000000\
+000000000\
+000000000\
+000000000";
+
+    const MULTI_SOLUTION_4X4: &str = "0000000000000000";
+
+    fn is_complete_solution(sudoku: &Sudoku, b: usize) -> bool {
+        let n = b * b;
+        let units = units(b);
+        units.iter().all(|unit| {
+            let mut seen = 0u32;
+            unit.iter().all(|&cell| match sudoku.assignment[cell] {
+                Variable::Assigned(val) if (1..=n as u8).contains(&val) => {
+                    let bit = 1 << val;
+                    let fresh = seen & bit == 0;
+                    seen |= bit;
+                    fresh
+                }
+                _ => false,
+            })
+        })
+    }
+
+    #[test]
+    fn solves_an_easy_9x9_puzzle() {
+        let sudoku: Sudoku = EASY_9X9.parse().unwrap();
+        let solved = sudoku.solve().expect("puzzle should be solvable");
+        assert!(is_complete_solution(&solved, 3));
+    }
+
+    #[test]
+    fn solves_a_hard_9x9_puzzle() {
+        let sudoku: Sudoku = HARD_9X9.parse().unwrap();
+        let solved = sudoku.solve().expect("puzzle should be solvable");
+        assert!(is_complete_solution(&solved, 3));
+    }
+
+    #[test]
+    fn a_well_formed_puzzle_is_unique() {
+        let sudoku: Sudoku = EASY_9X9.parse().unwrap();
+        assert!(sudoku.is_unique());
+    }
+
+    #[test]
+    fn an_empty_grid_is_not_unique() {
+        let sudoku: Sudoku = MULTI_SOLUTION_4X4.parse().unwrap();
+        assert!(!sudoku.is_unique());
+        assert_eq!(sudoku.solve_all(5).len(), 5);
+    }
+
+    #[test]
+    fn an_inconsistent_puzzle_has_no_solution() {
+        let sudoku: Sudoku = UNSOLVABLE_9X9.parse().unwrap();
+        assert!(sudoku.solve().is_none());
+    }
+
+    #[test]
+    fn rejects_a_value_out_of_range_for_the_grid() {
+        let mut chars: Vec<char> = "0".repeat(16).chars().collect();
+        chars[0] = '9';
+        let input: String = chars.into_iter().collect();
+        assert!(input.parse::<Sudoku>().is_err());
+    }
+
+    #[test]
+    fn rejects_input_whose_length_is_not_a_perfect_square() {
+        assert!("12345".parse::<Sudoku>().is_err());
+    }
+}